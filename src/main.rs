@@ -1,12 +1,11 @@
 use clap::{arg, Command};
 use std::fs;
+use std::io::Read;
 extern crate hex;
 
-const MAX_LEN:usize = 18446744073709551615;
-
 /*
  * From https://datatracker.ietf.org/doc/html/rfc1321#section-3.4
- * 
+ *
  * 64-element table T[1 ... 64] constructed from the
  * sine function. Let T[i] denote the i-th element of the table, which
  * is equal to the integer part of 4294967296 times abs(sin(i)), where i
@@ -34,17 +33,17 @@ const K: [u32; 64] = [
 
 /**
  * From https://datatracker.ietf.org/doc/html/rfc1321#section-3.3
- * 
+ *
  * A four-word buffer (A,B,C,D) is used to compute the message digest.
  * Here each of A, B, C, D is a 32-bit register. These registers are
  * initialized to the following values in hexadecimal, low-order bytes
  * first):
- * 
+ *
  *   word A: 01 23 45 67
  *   word B: 89 ab cd ef
  *   word C: fe dc ba 98
  *   word D: 76 54 32 10
- * 
+ *
  * Note: These are converted to little-endian literals
  */
 const A_INIT: u32 = 0x67452301;
@@ -73,7 +72,7 @@ struct State {
 impl Default for State {
 
     /**
-     * Default constructor; initializes each of the State fields 
+     * Default constructor; initializes each of the State fields
      * to the initial values from 3.3
      */
     fn default () -> State {
@@ -99,7 +98,92 @@ impl State {
     }
 }
 
-/** 
+/**
+ * Runs the 64-step compression function over a single 512-bit (64-byte)
+ * block, folding the result into `state`. This is the core of both the
+ * one-shot `hash` function and the incremental `Md5` hasher below.
+ */
+fn
+compress_block (state: &mut State, block: &[u8]) {
+
+    let a0 = state.a;
+    let b0 = state.b;
+    let c0 = state.c;
+    let d0 = state.d;
+
+    let mut m: [u32; 16] = [0; 16];
+    let mut indx = 0;
+
+    // Fill M array with 32-bit words from the outer 512-bit chunk
+    for int_chunk in block.chunks(4) {
+        let (b1, b2, b3, b4) = (int_chunk[0] as u32, int_chunk[1] as u32, int_chunk[2] as u32, int_chunk[3] as u32);
+        m[indx] = (b4 << 24) | (b3 << 16) | (b2 << 8) | b1;
+        indx += 1;
+    }
+
+    indx = 0;
+    let mut f:u32;
+
+    /*
+     * 64 iterations; 16 rounds each of the following four rounds from
+     * https://datatracker.ietf.org/doc/html/rfc1321#section-3.4
+     *
+     *  F(X,Y,Z) = XY v not(X) Z
+     *  G(X,Y,Z) = XZ v Y not(Z)
+     *  H(X,Y,Z) = X xor Y xor Z
+     *  I(X,Y,Z) = Y xor (X v not(Z))
+     */
+    while indx < 16 {
+        f = (state.d ^ (state.b & (state.c ^ state.d)))
+            .wrapping_add(state.a)
+            .wrapping_add(K[indx])
+            .wrapping_add(m[indx])
+            .rotate_left(SHIFT[indx]);
+
+        state.rotate(f);
+        indx += 1;
+    }
+
+    while indx < 32 {
+        f = (state.c ^ (state.d & (state.b ^ state.c)))
+            .wrapping_add(state.a)
+            .wrapping_add(K[indx])
+            .wrapping_add(m[(indx * 5 + 1) % 16])
+            .rotate_left(SHIFT[indx]);
+
+        state.rotate(f);
+        indx += 1;
+    }
+
+    while indx < 48 {
+        f = (state.b ^ state.c ^ state.d)
+            .wrapping_add(state.a)
+            .wrapping_add(K[indx])
+            .wrapping_add(m[(indx * 3 + 5) % 16])
+            .rotate_left(SHIFT[indx]);
+
+        state.rotate(f);
+        indx += 1;
+    }
+
+    while indx < 64 {
+        f = (state.c ^ (state.b | (!state.d)))
+            .wrapping_add(state.a)
+            .wrapping_add(K[indx])
+            .wrapping_add(m[(indx * 7) % 16])
+            .rotate_left(SHIFT[indx]);
+
+        state.rotate(f);
+        indx += 1;
+    }
+
+    state.a = state.a.wrapping_add(a0);
+    state.b = state.b.wrapping_add(b0);
+    state.c = state.c.wrapping_add(c0);
+    state.d = state.d.wrapping_add(d0);
+}
+
+/**
  * From https://datatracker.ietf.org/doc/html/rfc1321#section-3.1
  *
  * The message is "padded" (extended) so that its length (in bits) is
@@ -112,21 +196,23 @@ impl State {
  * message, and then "0" bits are appended so that the length in bits of
  * the padded message becomes congruent to 448, modulo 512. In all, at
  * least one bit and at most 512 bits are appended.
+ *
+ * `total_len_bits` is the bit length of the *entire* original message,
+ * which may be larger than `message.len()` when padding a trailing
+ * remainder left over from incremental hashing.
  */
 fn
-pad (message: &mut Vec<u8>) {
-    // Get message length in bits; length times 8 since message is in bytes
-    let mlen_in_bits = message.len() * 8 % MAX_LEN;
+pad (message: &mut Vec<u8>, total_len_bits: u64) {
 
     // Appends 1 << 7, ie 1000 0000, we're working in bytes
     message.push(0x80);
 
     // Padding to 448 modulo 512 bits
-    while (message.len() * 8 % MAX_LEN) % 512 != 448 {
+    while (message.len() * 8) % 512 != 448 {
         message.push(0x0);
     }
 
-    /* 
+    /*
     * From https://datatracker.ietf.org/doc/html/rfc1321#section-3.2
     *
     * A 64-bit representation of b (the length of the message before the
@@ -135,115 +221,152 @@ pad (message: &mut Vec<u8>) {
     * the low-order 64 bits of b are used. (These bits are appended as two
     * 32-bit words and appended low-order word first in accordance with the
     * previous conventions.)
-     
+
     * At this point the resulting message (after padding with bits and with
     * b) has a length that is an exact multiple of 512 bits. Equivalently,
     * this message has a length that is an exact multiple of 16 (32-bit)
     * words. Let M[0 ... N-1] denote the words of the resulting message,
     * where N is a multiple of 16.
     */
-    let len_in_bytes = mlen_in_bits.to_le_bytes();
-    message.extend_from_slice(&len_in_bytes);
+    message.extend_from_slice(&total_len_bits.to_le_bytes());
 }
 
-fn
-hash (message: &str) -> String {
+/**
+ * Streaming MD5 hasher. Unlike `hash`, which requires the entire message
+ * up front, `Md5` accepts data incrementally via repeated calls to
+ * `update`, buffering at most one partial 64-byte block between calls.
+ * This lets callers hash arbitrarily large inputs (eg. multi-gigabyte
+ * files) without holding the whole thing in memory.
+ */
+pub struct Md5 {
+    state: State,
+    buffer: Vec<u8>,
+    total_len: u64
+}
 
-    let mut state:State = Default::default();
-    let mut message_bytes = message.as_bytes().to_vec();
+impl Default for Md5 {
+    fn default () -> Md5 {
+        Md5::new()
+    }
+}
 
-    // Pad the input message according to specification, so that its length mod 512 == 0
-    pad(&mut message_bytes);
+impl Md5 {
 
-    // 512-bit chunks
-    for chunk in message_bytes.chunks(64) {
+    /**
+     * Constructs a fresh hasher with the initial state from 3.3 and an
+     * empty partial-block buffer.
+     */
+    pub fn new () -> Md5 {
+        Md5 {
+            state: Default::default(),
+            buffer: Vec::with_capacity(64),
+            total_len: 0
+        }
+    }
 
-        let a0 = state.a;
-        let b0 = state.b;
-        let c0 = state.c;
-        let d0 = state.d;
+    /**
+     * Feeds `data` into the hasher. Any complete 64-byte blocks are
+     * compressed immediately; a trailing remainder of fewer than 64
+     * bytes is kept in `buffer` until the next call, or until `finalize`.
+     */
+    pub fn update (&mut self, data: &[u8]) {
+        self.total_len = self.total_len.wrapping_add(data.len() as u64);
+        self.buffer.extend_from_slice(data);
+
+        let mut offset = 0;
+        while self.buffer.len() - offset >= 64 {
+            compress_block(&mut self.state, &self.buffer[offset..offset + 64]);
+            offset += 64;
+        }
+        self.buffer.drain(0..offset);
+    }
 
-        let mut m: [u32; 16] = [0; 16];
-        let mut indx = 0;
+    /**
+     * Pads the remaining buffered bytes and folds them into the state,
+     * then returns the 16-byte digest, low-order byte of A through
+     * high-order byte of D, as described in 3.5.
+     */
+    pub fn finalize (mut self) -> [u8; 16] {
+        let total_len_bits = self.total_len.wrapping_mul(8);
+        let mut tail = std::mem::take(&mut self.buffer);
+        pad(&mut tail, total_len_bits);
 
-        // Fill M array with 32-bit words from the outer 512-bit chunk
-        for int_chunk in chunk.chunks(4) {
-            let (b1, b2, b3, b4) = (int_chunk[0] as u32, int_chunk[1] as u32, int_chunk[2] as u32, int_chunk[3] as u32);
-            m[indx] = (b4 << 24) | (b3 << 16) | (b2 << 8) | b1;
-            indx += 1;
+        for block in tail.chunks(64) {
+            compress_block(&mut self.state, block);
         }
 
-        indx = 0;
-        let mut f:u32;
-
-        /*
-         * 64 iterations; 16 rounds each of the following four rounds from 
-         * https://datatracker.ietf.org/doc/html/rfc1321#section-3.4
-         * 
-         *  F(X,Y,Z) = XY v not(X) Z
-         *  G(X,Y,Z) = XZ v Y not(Z)
-         *  H(X,Y,Z) = X xor Y xor Z
-         *  I(X,Y,Z) = Y xor (X v not(Z))
-         */
-        while indx < 16 {
-            f = (state.d ^ (state.b & (state.c ^ state.d)))
-                .wrapping_add(state.a)
-                .wrapping_add(K[indx])
-                .wrapping_add(m[indx])
-                .rotate_left(SHIFT[indx]);
-
-            state.rotate(f);
-            indx += 1;
-        }
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.state.a.to_le_bytes());
+        bytes.extend_from_slice(&self.state.b.to_le_bytes());
+        bytes.extend_from_slice(&self.state.c.to_le_bytes());
+        bytes.extend_from_slice(&self.state.d.to_le_bytes());
 
-        while indx < 32 {
-            f = (state.c ^ (state.d & (state.b ^ state.c)))
-                .wrapping_add(state.a)
-                .wrapping_add(K[indx])
-                .wrapping_add(m[(indx * 5 + 1) % 16])
-                .rotate_left(SHIFT[indx]);
+        bytes.try_into().expect("Wrong length")
+    }
+}
 
-            state.rotate(f);
-            indx += 1;
-        }
+/**
+ * Computes the padding bytes that MD5 would append to a message of
+ * `total_len` bytes: a single 0x80 byte, zero bytes out to 56 mod 64
+ * bytes, then the 64-bit little-endian bit length, per 3.1/3.2. Unlike
+ * `pad`, this returns just the appended suffix rather than mutating a
+ * message in place, which is what a length-extension forgery needs:
+ * the "glue" between the original (unknown) message and the attacker's
+ * extension.
+ */
+fn
+glue_padding (total_len: usize) -> Vec<u8> {
 
-        while indx < 48 {
-            f = (state.b ^ state.c ^ state.d)
-                .wrapping_add(state.a)
-                .wrapping_add(K[indx])
-                .wrapping_add(m[(indx * 3 + 5) % 16])
-                .rotate_left(SHIFT[indx]);
+    let mut padding = Vec::new();
+    padding.push(0x80);
 
-            state.rotate(f);
-            indx += 1;
-        }
+    while (total_len + padding.len()) % 64 != 56 {
+        padding.push(0x0);
+    }
 
-        while indx < 64 {
-            f = (state.c ^ (state.b | (!state.d)))
-                .wrapping_add(state.a)
-                .wrapping_add(K[indx])
-                .wrapping_add(m[(indx * 7) % 16])
-                .rotate_left(SHIFT[indx]);
+    let total_len_bits = (total_len as u64).wrapping_mul(8);
+    padding.extend_from_slice(&total_len_bits.to_le_bytes());
 
-            state.rotate(f);
-            indx += 1;
-        }
+    padding
+}
+
+/**
+ * MD5 length-extension forgery.
+ *
+ * Because MD5 is a Merkle-Damgard construction, its final state IS the
+ * digest; an attacker who knows `MD5(secret || message)` and the byte
+ * length of `secret || message` can reconstruct the internal `State` at
+ * the point the original hashing finished, without ever learning
+ * `secret`. Resuming compression from that state over `extension`
+ * (after accounting for the glue padding the original message would
+ * have received) yields a valid digest for
+ * `secret || message || glue_padding || extension`.
+ *
+ * Returns the forged digest along with the suffix
+ * (`glue_padding || extension`) the caller must append to the original
+ * message for the forged digest to verify.
+ */
+pub fn
+extend (prev_digest: [u8; 16], already_hashed_len: usize, extension: &[u8]) -> ([u8; 16], Vec<u8>) {
 
-        state.a = state.a.wrapping_add(a0);
-        state.b = state.b.wrapping_add(b0);
-        state.c = state.c.wrapping_add(c0);
-        state.d = state.d.wrapping_add(d0);
+    // Reconstruct the state, the inverse of finalize's final to_le_bytes step
+    let mut state = State {
+        a: u32::from_le_bytes(prev_digest[0..4].try_into().unwrap()),
+        b: u32::from_le_bytes(prev_digest[4..8].try_into().unwrap()),
+        c: u32::from_le_bytes(prev_digest[8..12].try_into().unwrap()),
+        d: u32::from_le_bytes(prev_digest[12..16].try_into().unwrap())
+    };
+
+    let glue_pad = glue_padding(already_hashed_len);
+    let forged_len = already_hashed_len + glue_pad.len() + extension.len();
+
+    let mut tail = extension.to_vec();
+    pad(&mut tail, (forged_len as u64).wrapping_mul(8));
+
+    for block in tail.chunks(64) {
+        compress_block(&mut state, block);
     }
 
-    /*
-     * From https://datatracker.ietf.org/doc/html/rfc1321#section-3.5:
-     * 
-     * The message digest produced as output is A, B, C, D. That is, we
-     * begin with the low-order byte of A, and end with the high-order byte
-     * of D.
-     * 
-     * This section converts each u32 into 4 u8s, collecting all of the u8s into a Vec
-     */
     let mut bytes = Vec::new();
     bytes.extend_from_slice(&state.a.to_le_bytes());
     bytes.extend_from_slice(&state.b.to_le_bytes());
@@ -252,20 +375,235 @@ hash (message: &str) -> String {
 
     let digest: [u8; 16] = bytes.try_into().expect("Wrong length");
 
-    // Encode into base 64
-    return hex::encode(&digest); 
+    let mut suffix = glue_pad;
+    suffix.extend_from_slice(extension);
+
+    (digest, suffix)
+}
+
+// Block size MD5 operates on internally; HMAC pads/hashes the key to this size
+const HMAC_BLOCK_SIZE: usize = 64;
+
+/**
+ * Keyed-hash message authentication code over MD5, per RFC 2104.
+ *
+ * If `key` is longer than the block size it's first replaced with
+ * `MD5(key)`, then right-padded with zero bytes out to the block size.
+ * `ipad`/`opad` are that key XORed with the repeating bytes 0x36/0x5c,
+ * and the result is `MD5(opad || MD5(ipad || message))`, which binds
+ * the digest to `key` in a way length-extension (see `extend` above)
+ * cannot forge without knowing it.
+ */
+pub fn
+hmac_md5 (key: &[u8], message: &[u8]) -> [u8; 16] {
+
+    let mut key_block = if key.len() > HMAC_BLOCK_SIZE {
+        let mut hasher = Md5::new();
+        hasher.update(key);
+        hasher.finalize().to_vec()
+    } else {
+        key.to_vec()
+    };
+    key_block.resize(HMAC_BLOCK_SIZE, 0x0);
+
+    let ipad: Vec<u8> = key_block.iter().map(|b| b ^ 0x36).collect();
+    let opad: Vec<u8> = key_block.iter().map(|b| b ^ 0x5c).collect();
+
+    let mut inner = Md5::new();
+    inner.update(&ipad);
+    inner.update(message);
+    let inner_digest = inner.finalize();
+
+    let mut outer = Md5::new();
+    outer.update(&opad);
+    outer.update(&inner_digest);
+    outer.finalize()
+}
+
+fn
+hash (message: &[u8]) -> [u8; 16] {
+
+    let mut hasher = Md5::new();
+    hasher.update(message);
+    hasher.finalize()
+}
+
+// Standard base64 alphabet, per RFC 4648 section 4
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/**
+ * Encodes `data` as standard base64 with `=` padding. Used as an
+ * alternative to hex for digest output; MD5 digests are always 16
+ * bytes, but this works over any byte slice.
+ */
+fn
+base64_encode (data: &[u8]) -> String {
+
+    let mut encoded = String::new();
+
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = *chunk.get(1).unwrap_or(&0) as u32;
+        let b2 = *chunk.get(2).unwrap_or(&0) as u32;
+        let n = (b0 << 16) | (b1 << 8) | b2;
+
+        encoded.push(BASE64_ALPHABET[((n >> 18) & 0x3f) as usize] as char);
+        encoded.push(BASE64_ALPHABET[((n >> 12) & 0x3f) as usize] as char);
+        encoded.push(if chunk.len() > 1 { BASE64_ALPHABET[((n >> 6) & 0x3f) as usize] as char } else { '=' });
+        encoded.push(if chunk.len() > 2 { BASE64_ALPHABET[(n & 0x3f) as usize] as char } else { '=' });
+    }
+
+    encoded
 }
 
-fn 
+/**
+ * Renders a digest in the requested output encoding: lowercase hex
+ * (the default), uppercase hex, or standard base64.
+ */
+fn
+encode_digest (digest: &[u8; 16], encoding: &str) -> String {
+    match encoding {
+        "HEX" => hex::encode_upper(digest),
+        "base64" => base64_encode(digest),
+        _ => hex::encode(digest)
+    }
+}
+
+/**
+ * Hashes the file at `path` using the streaming `Md5` hasher, reading it
+ * in fixed-size chunks rather than loading it into memory all at once.
+ */
+fn
+hash_file (path: &str) -> std::io::Result<[u8; 16]> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Md5::new();
+    let mut buf = [0u8; 8192];
+
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+
+    Ok(hasher.finalize())
+}
+
+/**
+ * `md5sum -c` style checksum verification. Each line of the manifest at
+ * `path` is `<hex-digest>  <path>`; every referenced file is rehashed
+ * with the streaming hasher and compared against its recorded digest.
+ * Digests are compared as raw 16-byte arrays (via `hex::decode`) rather
+ * than as strings, so the manifest's hex case doesn't matter. A missing
+ * file or an unparseable digest counts as a failure for that entry
+ * rather than aborting the rest of the manifest. Prints `OK`/`FAILED`
+ * per entry plus a final summary, and returns whether every entry
+ * matched.
+ */
+fn
+check (path: &str) -> bool {
+    let manifest = fs::read_to_string(path)
+        .expect("Should have been able to read the checksum file");
+    let mut total = 0;
+    let mut failed = 0;
+
+    for line in manifest.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        total += 1;
+
+        let mut fields = line.splitn(2, char::is_whitespace);
+        let digest_hex = fields.next().expect("splitn(2, ..) always yields at least one field");
+        let file_path = match fields.next() {
+            Some(p) => p.trim(),
+            None => {
+                println!("{}: FAILED", line);
+                failed += 1;
+                continue;
+            }
+        };
+
+        let matches = hex::decode(digest_hex)
+            .ok()
+            .zip(hash_file(file_path).ok())
+            .is_some_and(|(expected, actual)| actual.as_slice() == expected.as_slice());
+
+        if matches {
+            println!("{}: OK", file_path);
+        } else {
+            println!("{}: FAILED", file_path);
+            failed += 1;
+        }
+    }
+
+    if failed == 0 {
+        println!("all {} files OK", total);
+    } else {
+        println!("{} of {} files FAILED", failed, total);
+    }
+
+    failed == 0
+}
+
+fn
 tests () {
-    assert!(hash("").eq("d41d8cd98f00b204e9800998ecf8427e"));
-    assert!(hash("abcde").eq("ab56b4d92b40713acc5af89985d4b786"));
-    assert!(hash("abcdefghijklmnopqrstuvwxyz123456789012345678901234567890").eq("68b7c41b350d85fe015fc2602f128c4c"));
+    assert_eq!(hex::encode(hash(b"")), "d41d8cd98f00b204e9800998ecf8427e");
+    assert_eq!(hex::encode(hash(b"abcde")), "ab56b4d92b40713acc5af89985d4b786");
+    assert_eq!(hex::encode(hash(b"abcdefghijklmnopqrstuvwxyz123456789012345678901234567890")), "68b7c41b350d85fe015fc2602f128c4c");
+
+    // Non-UTF8 input must hash fine now that the core operates on bytes
+    assert_eq!(hex::encode(hash(&[0xff, 0x00, 0xfe, 0x01])), "1cf64af3949b45723a0a989b153eb3b5");
+
+    // Uppercase hex and base64 are alternate encodings of the same digest
+    let digest = hash(b"abcde");
+    assert_eq!(encode_digest(&digest, "hex"), "ab56b4d92b40713acc5af89985d4b786");
+    assert_eq!(encode_digest(&digest, "HEX"), "AB56B4D92B40713ACC5AF89985D4B786");
+    assert_eq!(encode_digest(&digest, "base64"), "q1a02StAcTrMWviZhdS3hg==");
+
+    // The incremental hasher, fed in arbitrary pieces, must agree with the one-shot hash
+    let mut incremental = Md5::new();
+    incremental.update(b"abcde");
+    assert_eq!(incremental.finalize(), hash(b"abcde"));
+
+    let mut incremental = Md5::new();
+    incremental.update(b"abcdefghijklmnop");
+    incremental.update(b"qrstuvwxyz1234567890123456789012345");
+    incremental.update(b"67890");
+    assert_eq!(incremental.finalize(), hash(b"abcdefghijklmnopqrstuvwxyz123456789012345678901234567890"));
+
+    // Length-extension: forge a digest for secret||message||glue_padding||extension
+    // without ever knowing `secret`
+    let secret = b"top-secret-key";
+    let message = b"count=10&lat=37.351&user_id=1&long=-119.827&waffle=eggo";
+    let mut secret_message = secret.to_vec();
+    secret_message.extend_from_slice(message);
+
+    let mut victim_hasher = Md5::new();
+    victim_hasher.update(&secret_message);
+    let victim_digest = victim_hasher.finalize();
+
+    let extension = b"&waffle=liege";
+    let (forged_digest, suffix) = extend(victim_digest, secret_message.len(), extension);
+
+    let mut forged_message = secret_message.clone();
+    forged_message.extend_from_slice(&suffix);
+    let mut forged_hasher = Md5::new();
+    forged_hasher.update(&forged_message);
+    assert_eq!(forged_hasher.finalize(), forged_digest);
+
+    // RFC 2104 test vector for HMAC-MD5 ("key", "The quick brown fox jumps over the lazy dog")
+    assert_eq!(
+        hex::encode(hmac_md5(b"key", b"The quick brown fox jumps over the lazy dog")),
+        "80070713463e7749b90c2dc24911e275"
+    );
 
     println!("tests completed successfully!");
 }
 
-fn 
+fn
 main () {
     let matches = Command::new("md5")
     .version("0.1")
@@ -273,22 +611,42 @@ main () {
     .arg(arg!(--path <VALUE>).required(false))
     .arg(arg!(--string <VALUE>).required(false))
     .arg(arg!(--test).required(false))
+    .arg(arg!(--"hmac-key" <VALUE>).required(false))
+    .arg(arg!(--encoding <VALUE>).required(false))
+    .arg(arg!(--check <VALUE>).required(false))
     .get_matches();
 
     let string = matches.get_one::<String>("string");
     let path = matches.get_one::<String>("path");
     let test = matches.get_one::<bool>("test");
+    let hmac_key = matches.get_one::<String>("hmac-key");
+    let encoding = matches.get_one::<String>("encoding").map(|s| s.as_str()).unwrap_or("hex");
+    let check_file = matches.get_one::<String>("check");
+
+    if let Some(manifest) = check_file {
+        if !check(manifest) {
+            std::process::exit(1);
+        }
+        return;
+    }
 
     match (string, path, test) {
         (Some(text), None, Some(false)) => {
-            let digest = hash(&text);
-            println!("{}", digest);
+            let digest = match hmac_key {
+                Some(key) => hmac_md5(key.as_bytes(), text.as_bytes()),
+                None => hash(text.as_bytes())
+            };
+            println!("{}", encode_digest(&digest, encoding));
         },
         (None, Some(f), Some(false)) => {
-            let contents = fs::read_to_string(f)
-                .expect("Should have been able to read the file");
-            let digest = hash(&contents);
-            println!("{}", digest);
+            let digest = match hmac_key {
+                Some(key) => {
+                    let contents = fs::read(f).expect("Should have been able to read the file");
+                    hmac_md5(key.as_bytes(), &contents)
+                },
+                None => hash_file(f).expect("Should have been able to read the file")
+            };
+            println!("{}", encode_digest(&digest, encoding));
         },
         (None, None, Some(true)) => {
             tests();